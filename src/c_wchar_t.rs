@@ -4,67 +4,35 @@ pub type c_wchar_t = c_wchar_t_definition::c_wchar_t;
 
 // Validate that our definition is consistent with libc's definition, without
 // introducing a dependency on libstd in ordinary builds.
+//
+// We only check width here, not signedness: `libc::wchar_t` is signed on
+// most non-Windows targets (e.g. `i32` on Linux) while we deliberately keep
+// `c_wchar_t` unsigned to match the unsigned code unit/code point values
+// that actually flow through this type, so a check that the two types are
+// identical would not compile on those targets.
 #[cfg(all(test, feature = "std"))]
-const _: self::c_char = 0 as libc::wchar_t;
+const _: fn() = || {
+    let _: [(); core::mem::size_of::<c_wchar_t>()] =
+        [(); core::mem::size_of::<libc::wchar_t>()];
+};
 
 #[allow(dead_code)]
 mod c_wchar_t_definition {
-    // These are the targets on which c_char is unsigned.
-    #[cfg(any(
-        all(
-            target_os = "linux",
-            any(
-                target_arch = "aarch64",
-                target_arch = "arm",
-                target_arch = "hexagon",
-                target_arch = "powerpc",
-                target_arch = "powerpc64",
-                target_arch = "s390x",
-                target_arch = "riscv64",
-                target_arch = "riscv32"
-            )
-        ),
-        all(
-            target_os = "android",
-            any(target_arch = "aarch64", target_arch = "arm")
-        ),
-        all(target_os = "l4re", target_arch = "x86_64"),
-        all(
-            target_os = "freebsd",
-            any(
-                target_arch = "aarch64",
-                target_arch = "arm",
-                target_arch = "powerpc",
-                target_arch = "powerpc64",
-                target_arch = "riscv64"
-            )
-        ),
-        all(
-            target_os = "netbsd",
-            any(target_arch = "aarch64", target_arch = "arm", target_arch = "powerpc")
-        ),
-        all(target_os = "openbsd", target_arch = "aarch64"),
-        all(
-            target_os = "vxworks",
-            any(
-                target_arch = "aarch64",
-                target_arch = "arm",
-                target_arch = "powerpc64",
-                target_arch = "powerpc"
-            )
-        ),
-        all(target_os = "fuchsia", target_arch = "aarch64")
-    ))]
-    pub use self::unsigned::c_char;
+    // On Windows (MSVC and MinGW), std::wstring's wchar_t is 16 bits wide and
+    // holds UTF-16 code units.
+    #[cfg(target_os = "windows")]
+    pub use self::utf16::c_wchar_t;
 
-    // On every other target, c_char is signed.
-    pub use self::signed::*;
+    // On every other target, wchar_t is 32 bits wide and holds UTF-32 code
+    // points.
+    #[cfg(not(target_os = "windows"))]
+    pub use self::utf32::c_wchar_t;
 
-    mod unsigned {
-        pub type c_wchar_t = u32;
+    mod utf16 {
+        pub type c_wchar_t = u16;
     }
 
-    mod signed {
-        pub type c_wchar_t = i32;
+    mod utf32 {
+        pub type c_wchar_t = u32;
     }
 }
@@ -1,7 +1,7 @@
 use crate::actually_private::Private;
 use crate::UniquePtr;
-// #[cfg(feature = "alloc")]
-// use alloc::borrow::Cow;
+#[cfg(feature = "alloc")]
+use alloc::borrow::Cow;
 #[cfg(feature = "alloc")]
 use alloc::string::String;
 use core::cmp::Ordering;
@@ -13,13 +13,35 @@ use core::pin::Pin;
 use core::slice;
 use core::str::{self};
 
-use widestring::{U32CStr, U32CString, Utf32Str, Utf32String};
+#[cfg(feature = "std")]
+use std::ffi::{OsStr, OsString};
+#[cfg(all(feature = "std", target_os = "windows"))]
+use std::os::windows::ffi::{OsStrExt, OsStringExt};
+
+use widestring::{U32CStr, U32CString, U32Str, U32String, Utf32Str, Utf32String};
 
-/// In C++, wchar_t may be signed or unsigned, but is in practice signed.
-/// In Rust UTF32String, its wchar_t is unsigned.
-/// Use unsigned to ease interop.
-type wchar_t = u32;
+use crate::c_wchar_t::c_wchar_t;
 
+/// The element type backing C++ `std::wstring`'s storage.
+///
+/// On Windows (MSVC and MinGW), `wchar_t` is 16 bits wide and `std::wstring`
+/// stores UTF-16 code units. Everywhere else, `wchar_t` is 32 bits wide and
+/// `std::wstring` stores UTF-32 code points, one per `char`. The char-facing
+/// API re-encodes across this difference at the boundary; [`as_wchars`] and
+/// [`as_ptr`] are the only places the native unit width leaks through.
+///
+/// [`as_wchars`]: CxxWString::as_wchars
+/// [`as_ptr`]: CxxWString::as_ptr
+type wchar_t = c_wchar_t;
+
+// These shims assume the C++ side (`cxx.cc`, not part of this source tree)
+// indexes the `std::wstring` buffer through the same `wchar_t`-width pointer
+// type Rust does here: `uint16_t*` on Windows, `uint32_t*` everywhere else.
+// If the C++ implementation still treats every buffer as 32-bit units, the
+// element width silently mismatches on Windows and corrupts memory, since
+// each side would stride through the buffer at a different element size.
+// Any change to `wchar_t`'s definition in `c_wchar_t.rs` must be paired with
+// a matching update to those shims.
 extern "C" {
     #[link_name = "cxxbridge1$cxx_wstring$init"]
     fn wstring_init(this: &mut MaybeUninit<CxxWString>, ptr: *const wchar_t, len: usize);
@@ -128,16 +150,39 @@ impl CxxWString {
         unsafe { slice::from_raw_parts(data, len) }
     }
 
-    /// Returns a char slice of this string's contents.
-    pub fn as_chars(&self) -> &[char] {
-        let data = self.as_ptr();
-        let len = self.len();
-        unsafe { slice::from_raw_parts(data as *const char, len) }
+    /// Decodes this string's units into Rust `char`s, validating each one.
+    ///
+    /// Returns every decoded `char` if all units are well-formed, or the
+    /// index of the first invalid unit otherwise: a lone surrogate
+    /// (0xD800-0xDFFF) or, on the targets where the backing store is
+    /// UTF-32, a unit above `char::MAX`.
+    fn decode_checked(&self) -> Result<std::vec::Vec<char>, WideCharError> {
+        #[cfg(target_os = "windows")]
+        {
+            decode_utf16_units_checked(self.as_wchars())
+        }
+        #[cfg(not(target_os = "windows"))]
+        {
+            decode_u32_units_checked(self.as_wchars())
+        }
     }
 
-    /// Helper to construct a char iterator, simplifying some other methods.
+    /// Helper to construct a char iterator for methods (equality with `str`
+    /// and the `widestring` types, lossy conversion to `String`) that must
+    /// not fail even over ill-formed data; invalid units are decoded
+    /// lossily rather than propagating an error.
     fn as_char_iter(&self) -> impl Iterator<Item = char> + '_ {
-        self.as_chars().iter().copied()
+        #[cfg(target_os = "windows")]
+        {
+            char::decode_utf16(self.as_wchars().iter().copied())
+                .map(|unit| unit.unwrap_or(char::REPLACEMENT_CHARACTER))
+        }
+        #[cfg(not(target_os = "windows"))]
+        {
+            self.as_wchars()
+                .iter()
+                .map(|&unit| char::from_u32(unit).unwrap_or(char::REPLACEMENT_CHARACTER))
+        }
     }
 
     /// Produces a pointer to the first character of the string.
@@ -155,26 +200,34 @@ impl CxxWString {
         unsafe { wstring_data(self) }
     }
 
-    /// Validates that the C++ string contains UTF-8 data and produces a view of
-    /// it as a Rust &amp;str, otherwise an error.
-    // pub fn to_str(&self) -> Result<&str, Utf8Error> {
-    //     str::from_utf8(self.as_bytes())
-    // }
-    pub fn to_str(&self) -> String {
-        return self.as_chars().iter().collect();
+    /// Validates that the C++ string contains only well-formed Unicode
+    /// scalar values and produces a Rust `String`, otherwise an error
+    /// identifying the first invalid unit.
+    pub fn to_str(&self) -> Result<String, WideCharError> {
+        self.decode_checked()
+            .map(|chars| chars.into_iter().collect())
     }
 
-    /// If the contents of the C++ string are valid UTF-8, this function returns
-    /// a view as a Cow::Borrowed &amp;str. Otherwise replaces any invalid UTF-8
-    /// sequences with the U+FFFD [replacement character] and returns a
-    /// Cow::Owned String.
+    /// If the contents of the C++ string are entirely well-formed, this
+    /// function returns a Cow::Owned String built from it directly.
+    /// Otherwise replaces any invalid unit (a lone surrogate, or, where the
+    /// backing store is UTF-32, a value above `char::MAX`) with the U+FFFD
+    /// [replacement character] and returns a Cow::Owned String.
+    ///
+    /// Unlike [`CxxString::to_string_lossy`][crate::CxxString::to_string_lossy],
+    /// this can never return `Cow::Borrowed`: the wide string's backing
+    /// store is UTF-16 or UTF-32 units, never UTF-8 bytes, so there is no
+    /// in-place `&str` to borrow.
     ///
     /// [replacement character]: https://doc.rust-lang.org/std/char/constant.REPLACEMENT_CHARACTER.html
-    // #[cfg(feature = "alloc")]
-    // #[cfg_attr(doc_cfg, doc(cfg(feature = "alloc")))]
-    // pub fn to_string_lossy(&self) -> Cow<str> {
-    //     String::from_utf8_lossy(self.as_bytes())
-    // }
+    #[cfg(feature = "alloc")]
+    pub fn to_string_lossy(&self) -> Cow<str> {
+        // `as_char_iter` already decodes lossily in a single pass over the
+        // buffer, substituting U+FFFD for invalid units, so there is no
+        // need to first attempt a strict `decode_checked` pass and only
+        // fall back to this on error.
+        Cow::Owned(self.as_char_iter().collect())
+    }
 
     /// Removes all characters from the string.
     ///
@@ -221,17 +274,41 @@ impl CxxWString {
 
     /// Appends a given string slice onto the end of this C++ string.
     pub fn push_str(self: Pin<&mut Self>, s: &str) {
-        let chars = s.chars().collect::<std::vec::Vec<_>>();
-        self.push_chars(&chars);
+        #[cfg(target_os = "windows")]
+        {
+            let units = s.encode_utf16().collect::<std::vec::Vec<_>>();
+            unsafe { wstring_push(self, units.as_ptr(), units.len()) }
+        }
+        #[cfg(not(target_os = "windows"))]
+        {
+            let chars = s.chars().collect::<std::vec::Vec<_>>();
+            self.push_chars(&chars);
+        }
     }
 
     /// Appends arbitrary chars onto the end of this C++ string.
     pub fn push_chars(self: Pin<&mut Self>, chars: &[char]) {
-        unsafe { wstring_push(self, chars.as_ptr() as *const wchar_t, chars.len()) }
+        #[cfg(target_os = "windows")]
+        {
+            let units = encode_utf16_units(chars);
+            unsafe { wstring_push(self, units.as_ptr(), units.len()) }
+        }
+        #[cfg(not(target_os = "windows"))]
+        unsafe {
+            wstring_push(self, chars.as_ptr() as *const wchar_t, chars.len())
+        }
     }
 
     /// Create a UniquePtr<CxxWString> from a slice of chars.
     pub fn create(chars: &[char]) -> UniquePtr<Self> {
+        #[cfg(target_os = "windows")]
+        {
+            let units = encode_utf16_units(chars);
+            unsafe {
+                UniquePtr::from_raw(wstring_new(units.as_ptr(), units.len()) as *mut CxxWString)
+            }
+        }
+        #[cfg(not(target_os = "windows"))]
         unsafe {
             UniquePtr::from_raw(
                 wstring_new(chars.as_ptr() as *const wchar_t, chars.len()) as *mut CxxWString
@@ -240,15 +317,120 @@ impl CxxWString {
     }
 }
 
+/// Encodes chars into UTF-16 code units, for targets where `wchar_t` is
+/// 16 bits wide.
+#[cfg(target_os = "windows")]
+fn encode_utf16_units(chars: &[char]) -> std::vec::Vec<wchar_t> {
+    let mut units = std::vec::Vec::with_capacity(chars.len());
+    for &c in chars {
+        let mut buf = [0u16; 2];
+        units.extend_from_slice(c.encode_utf16(&mut buf));
+    }
+    units
+}
+
+/// Lossily decodes raw (possibly ill-formed) UTF-32 units, as produced by
+/// the `widestring` crate's unchecked `U32Str`/`U32CStr`, the same way
+/// [`CxxWString::as_char_iter`] treats its own possibly ill-formed data.
+fn decode_u32_units_lossy(units: &[u32]) -> impl Iterator<Item = char> + '_ {
+    units
+        .iter()
+        .map(|&unit| char::from_u32(unit).unwrap_or(char::REPLACEMENT_CHARACTER))
+}
+
+/// Validates raw UTF-32 units into `char`s, used by
+/// [`CxxWString::decode_checked`] on targets where the backing store is
+/// already UTF-32. Factored out as a free function, rather than inlined
+/// into the method, so it can be exercised directly in tests without
+/// going through the C++ side of `CxxWString`.
+#[cfg(not(target_os = "windows"))]
+fn decode_u32_units_checked(units: &[u32]) -> Result<std::vec::Vec<char>, WideCharError> {
+    units
+        .iter()
+        .enumerate()
+        .map(|(index, &unit)| char::from_u32(unit).ok_or(WideCharError { valid_up_to: index }))
+        .collect()
+}
+
+/// Validates raw UTF-16 units into `char`s, used by
+/// [`CxxWString::decode_checked`] on targets where the backing store is
+/// UTF-16; see [`decode_u32_units_checked`] for why this is a free function.
+///
+/// `char::decode_utf16` yields one `Result` per decoded scalar value, but a
+/// valid surrogate pair consumes two raw units for that one output item, so
+/// `.enumerate()` over its output would report the wrong raw offset for
+/// anything after the first surrogate pair. Track the raw unit position
+/// explicitly instead: every `Err` (an unpaired surrogate) consumes exactly
+/// one raw unit, and every `Ok` consumes two units if it came from a
+/// surrogate pair (scalar value at or above `0x10000`) or one unit otherwise.
+#[cfg(target_os = "windows")]
+fn decode_utf16_units_checked(units: &[u16]) -> Result<std::vec::Vec<char>, WideCharError> {
+    let mut raw_index = 0;
+    char::decode_utf16(units.iter().copied())
+        .map(|unit| {
+            let index = raw_index;
+            match unit {
+                Ok(c) => {
+                    raw_index += if c as u32 >= 0x10000 { 2 } else { 1 };
+                    Ok(c)
+                }
+                Err(_) => {
+                    raw_index += 1;
+                    Err(WideCharError { valid_up_to: index })
+                }
+            }
+        })
+        .collect()
+}
+
+/// Builds a `CxxWString` from raw UTF-32 units such as those backing
+/// `U32Str`, `U32CStr`, and `Utf32Str`, without an intermediate `Vec<char>`
+/// allocation on the targets where `wchar_t` is already 32 bits wide.
+fn wstring_from_u32_units(units: &[u32]) -> UniquePtr<CxxWString> {
+    #[cfg(not(target_os = "windows"))]
+    unsafe {
+        UniquePtr::from_raw(
+            wstring_new(units.as_ptr() as *const wchar_t, units.len()) as *mut CxxWString
+        )
+    }
+    #[cfg(target_os = "windows")]
+    {
+        let chars = decode_u32_units_lossy(units).collect::<std::vec::Vec<_>>();
+        CxxWString::create(&chars)
+    }
+}
+
 impl Display for CxxWString {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        f.write_str(&self.to_str())
+        f.write_str(&self.to_string_lossy())
     }
 }
 
 impl Debug for CxxWString {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        f.write_str(&self.to_str())
+        f.write_str(&self.to_string_lossy())
+    }
+}
+
+/// Writing `write!(s, ...)` to a `Pin<&mut CxxWString>` encodes each piece
+/// into a temporary buffer of the backing store's native width, reserves
+/// capacity for it up front to amortize reallocations, and pushes it in a
+/// single call.
+impl fmt::Write for Pin<&mut CxxWString> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        #[cfg(target_os = "windows")]
+        {
+            let units = s.encode_utf16().collect::<std::vec::Vec<_>>();
+            self.as_mut().reserve(units.len());
+            unsafe { wstring_push(self.as_mut(), units.as_ptr(), units.len()) }
+        }
+        #[cfg(not(target_os = "windows"))]
+        {
+            let chars = s.chars().collect::<std::vec::Vec<_>>();
+            self.as_mut().reserve(chars.len());
+            unsafe { wstring_push(self.as_mut(), chars.as_ptr() as *const wchar_t, chars.len()) }
+        }
+        Ok(())
     }
 }
 
@@ -275,41 +457,198 @@ macro_rules! impl_partial_eq {
         $(
             impl PartialEq<$ty> for CxxWString {
                 fn eq(&self, other: &$ty) -> bool {
-                    self.as_wchars() == other.as_slice()
+                    self.as_char_iter().eq(decode_u32_units_lossy(other.as_slice()))
                 }
             }
 
             impl PartialEq<CxxWString> for $ty {
                 fn eq(&self, other: &CxxWString) -> bool {
-                    self.as_slice() == other.as_wchars()
+                    decode_u32_units_lossy(self.as_slice()).eq(other.as_char_iter())
                 }
             }
         )*
     }
 }
 
-impl_partial_eq!(U32CStr, U32CString, Utf32Str, Utf32String);
+impl_partial_eq!(U32CStr, U32CString, U32Str, U32String, Utf32Str, Utf32String);
+
+/// Builds a new `std::wstring` from [`U32CStr`]'s raw UTF-32 units.
+///
+/// This is infallible and, like [`CxxWString::create`], never validates the
+/// incoming units: on targets where `wchar_t` is 32 bits wide they are fed
+/// straight into the C++ constructor with no intermediate `Vec<char>`
+/// allocation, and on Windows they're lossily re-encoded to UTF-16,
+/// substituting U+FFFD for any unit that isn't a valid Unicode scalar value.
+/// Use [`TryFrom<&CxxWString>`] on the way back if you need to detect
+/// ill-formed data instead of silently replacing it.
+impl From<&U32CStr> for UniquePtr<CxxWString> {
+    fn from(value: &U32CStr) -> Self {
+        wstring_from_u32_units(value.as_slice())
+    }
+}
+
+/// Builds a new `std::wstring` from [`U32Str`]'s raw UTF-32 units.
+///
+/// See [`From<&U32CStr>`] above for the allocation and lossy-encoding
+/// behavior this shares.
+impl From<&U32Str> for UniquePtr<CxxWString> {
+    fn from(value: &U32Str) -> Self {
+        wstring_from_u32_units(value.as_slice())
+    }
+}
+
+/// Builds a new `std::wstring` from [`Utf32Str`]'s already-validated
+/// `char`s.
+///
+/// Unlike the `U32Str`/`U32CStr` conversions above, the incoming data is
+/// already known to be well-formed Unicode scalar values, so this never
+/// needs to re-encode lossily except when re-encoding down to UTF-16 on
+/// Windows (which cannot fail, since every `char` has a UTF-16 encoding).
+impl From<&Utf32Str> for UniquePtr<CxxWString> {
+    fn from(value: &Utf32Str) -> Self {
+        wstring_from_u32_units(value.as_slice())
+    }
+}
+
+/// Builds a new `std::wstring` from an [`OsStr`].
+///
+/// On Windows, the `OsStr`'s UTF-16 units are copied into the backing
+/// buffer directly. Elsewhere, `OsStr` is decoded lossily into `char`s
+/// (substituting U+FFFD for any ill-formed data) before being handed to
+/// [`CxxWString::create`].
+#[cfg(feature = "std")]
+impl From<&OsStr> for UniquePtr<CxxWString> {
+    fn from(value: &OsStr) -> Self {
+        #[cfg(target_os = "windows")]
+        {
+            let units = value.encode_wide().collect::<std::vec::Vec<_>>();
+            unsafe {
+                UniquePtr::from_raw(wstring_new(units.as_ptr(), units.len()) as *mut CxxWString)
+            }
+        }
+        #[cfg(not(target_os = "windows"))]
+        {
+            let chars = value
+                .to_string_lossy()
+                .chars()
+                .collect::<std::vec::Vec<_>>();
+            CxxWString::create(&chars)
+        }
+    }
+}
+
+/// Copies a `std::wstring`'s contents into a [`U32String`], validating each
+/// unit.
+///
+/// Unlike the `From` impls above, which silently substitute U+FFFD for
+/// ill-formed data, this rejects it: a lone surrogate or, on the targets
+/// where the backing store is UTF-32, a unit above `char::MAX` produces
+/// `Err(WideCharError)` identifying the first invalid unit, the same
+/// validation [`CxxWString::to_str`] performs. This validates identically on
+/// every target, even though on non-Windows targets the backing store is
+/// already UTF-32 units and could otherwise be copied through unchecked.
+impl TryFrom<&CxxWString> for U32String {
+    type Error = WideCharError;
+
+    fn try_from(value: &CxxWString) -> Result<Self, Self::Error> {
+        let units = value
+            .decode_checked()?
+            .into_iter()
+            .map(|c| c as u32)
+            .collect::<std::vec::Vec<_>>();
+        Ok(U32String::from_vec(units))
+    }
+}
+
+/// Copies a `std::wstring`'s contents into a [`Utf32String`], validating
+/// each unit the same way the `U32String` conversion above does.
+impl TryFrom<&CxxWString> for Utf32String {
+    type Error = WideCharError;
+
+    fn try_from(value: &CxxWString) -> Result<Self, Self::Error> {
+        Ok(value.decode_checked()?.into_iter().collect())
+    }
+}
+
+/// Copies a `std::wstring`'s contents into an [`OsString`], validating each
+/// unit on non-Windows targets (where the conversion has to decode UTF-32
+/// units into `char`s). On Windows, `OsString`'s own UTF-16 representation
+/// matches the backing buffer exactly, so the units are copied through
+/// directly and this can never fail there.
+#[cfg(feature = "std")]
+impl TryFrom<&CxxWString> for OsString {
+    type Error = WideCharError;
+
+    fn try_from(value: &CxxWString) -> Result<Self, Self::Error> {
+        #[cfg(target_os = "windows")]
+        {
+            Ok(OsString::from_wide(value.as_wchars()))
+        }
+        #[cfg(not(target_os = "windows"))]
+        {
+            Ok(OsString::from(value.to_str()?))
+        }
+    }
+}
 
 impl Eq for CxxWString {}
 
+// `PartialOrd`/`Ord`/`Hash` compare and hash the raw units from
+// `as_wchars()`, the same data `PartialEq` compares above, rather than
+// going through `as_char_iter()`'s lossy decode. Decoding substitutes
+// U+FFFD for invalid units (and, on Windows, merges surrogate pairs), so
+// two `CxxWString`s with different raw invalid units can decode to the
+// same `char` sequence; comparing/hashing the decoded form would make
+// those distinct-under-`PartialEq` strings compare `Equal` and hash
+// identically, violating the `Eq`/`Ord`/`Hash` consistency `BTreeSet`,
+// sorted `binary_search`, and `HashSet` all rely on.
 impl PartialOrd for CxxWString {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        self.as_chars().partial_cmp(other.as_chars())
+        self.as_wchars().partial_cmp(other.as_wchars())
     }
 }
 
 impl Ord for CxxWString {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.as_chars().cmp(other.as_chars())
+        self.as_wchars().cmp(other.as_wchars())
     }
 }
 
 impl Hash for CxxWString {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        self.as_chars().hash(state);
+        self.as_wchars().hash(state);
     }
 }
 
+/// Error returned by [`CxxWString::to_str`] when the string's contents are
+/// not entirely valid Unicode scalar values.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct WideCharError {
+    valid_up_to: usize,
+}
+
+impl WideCharError {
+    /// Returns the index, in raw units of the backing store (as returned by
+    /// [`CxxWString::as_wchars`]), of the first unit that is not
+    /// well-formed.
+    pub fn valid_up_to(&self) -> usize {
+        self.valid_up_to
+    }
+}
+
+impl Display for WideCharError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "invalid wide character sequence, valid up to {}",
+            self.valid_up_to
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for WideCharError {}
+
 #[doc(hidden)]
 #[repr(C)]
 pub struct StackWString {
@@ -328,9 +667,63 @@ impl StackWString {
 
     pub unsafe fn init(&mut self, value: impl AsRef<[char]>) -> Pin<&mut CxxWString> {
         let value = value.as_ref();
+        #[cfg(target_os = "windows")]
+        let units = encode_utf16_units(value);
+        #[cfg(not(target_os = "windows"))]
+        let units = value;
+        unsafe { self.init_wchars(units.as_ptr() as *const wchar_t, units.len()) }
+    }
+
+    /// Initializes from raw (possibly ill-formed) UTF-32 units, such as
+    /// from [`U32Str`] or [`U32CStr`], on the stack, the stack-allocated
+    /// counterpart to the buffer-reusing heap `From` conversions above.
+    ///
+    /// Unlike [`let_cxx_wstring!`], which hides its `unsafe` block behind
+    /// safe macro expansion, this is a raw entry point: callers are
+    /// responsible for the same invariants as [`init`][Self::init] (the
+    /// `StackWString` must not have already been initialized, and must
+    /// not outlive the returned `Pin`).
+    ///
+    /// [`U32Str`]: widestring::U32Str
+    /// [`U32CStr`]: widestring::U32CStr
+    pub unsafe fn init_u32(&mut self, value: &[u32]) -> Pin<&mut CxxWString> {
+        #[cfg(not(target_os = "windows"))]
+        unsafe {
+            self.init_wchars(value.as_ptr() as *const wchar_t, value.len())
+        }
+        #[cfg(target_os = "windows")]
+        {
+            let chars = decode_u32_units_lossy(value).collect::<std::vec::Vec<_>>();
+            unsafe { self.init(chars) }
+        }
+    }
+
+    /// Initializes from an [`OsStr`] on the stack; see [`init_u32`] for
+    /// the same caveat about this being a raw, unsafe entry point rather
+    /// than macro sugar.
+    ///
+    /// [`init_u32`]: Self::init_u32
+    #[cfg(feature = "std")]
+    pub unsafe fn init_os_str(&mut self, value: &OsStr) -> Pin<&mut CxxWString> {
+        #[cfg(target_os = "windows")]
+        {
+            let units = value.encode_wide().collect::<std::vec::Vec<_>>();
+            unsafe { self.init_wchars(units.as_ptr(), units.len()) }
+        }
+        #[cfg(not(target_os = "windows"))]
+        {
+            let chars = value
+                .to_string_lossy()
+                .chars()
+                .collect::<std::vec::Vec<_>>();
+            unsafe { self.init(chars) }
+        }
+    }
+
+    unsafe fn init_wchars(&mut self, ptr: *const wchar_t, len: usize) -> Pin<&mut CxxWString> {
         unsafe {
             let this = &mut *self.space.as_mut_ptr().cast::<MaybeUninit<CxxWString>>();
-            wstring_init(this, value.as_ptr() as *const wchar_t, value.len());
+            wstring_init(this, ptr, len);
             Pin::new_unchecked(&mut *this.as_mut_ptr())
         }
     }
@@ -344,3 +737,144 @@ impl Drop for StackWString {
         }
     }
 }
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wchar_t_matches_platform_width() {
+        #[cfg(target_os = "windows")]
+        assert_eq!(core::mem::size_of::<wchar_t>(), 2);
+        #[cfg(not(target_os = "windows"))]
+        assert_eq!(core::mem::size_of::<wchar_t>(), 4);
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn decode_u32_units_checked_rejects_lone_surrogate() {
+        let units = ['h' as u32, 0xD800, 'i' as u32];
+        let err = decode_u32_units_checked(&units).unwrap_err();
+        assert_eq!(err.valid_up_to(), 1);
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn decode_u32_units_checked_rejects_out_of_range() {
+        let units = [0x110000];
+        let err = decode_u32_units_checked(&units).unwrap_err();
+        assert_eq!(err.valid_up_to(), 0);
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn decode_u32_units_checked_accepts_well_formed() {
+        let units = ['h' as u32, 'i' as u32];
+        let chars: std::vec::Vec<char> = decode_u32_units_checked(&units).unwrap();
+        assert_eq!(chars, ['h', 'i']);
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn decode_utf16_units_checked_rejects_lone_surrogate() {
+        let units = ['h' as u16, 0xD800, 'i' as u16];
+        let err = decode_utf16_units_checked(&units).unwrap_err();
+        assert_eq!(err.valid_up_to(), 1);
+    }
+
+    #[test]
+    fn write_str_appends_formatted_text() {
+        use core::fmt::Write as _;
+
+        let mut stack = StackWString::new();
+        let mut s = unsafe { stack.init(['a']) };
+        write!(s, "{}{}", "b", 3).unwrap();
+        assert_eq!(s.to_str().unwrap(), "ab3");
+    }
+
+    #[test]
+    fn from_u32_cstr_round_trips() {
+        let owned = U32CString::from_vec(vec!['h' as u32, 'i' as u32]).unwrap();
+        let s = UniquePtr::<CxxWString>::from(owned.as_ucstr());
+        assert_eq!(s.to_str().unwrap(), "hi");
+    }
+
+    #[test]
+    fn from_u32_str_round_trips() {
+        let units = ['h' as u32, 'i' as u32];
+        let s = UniquePtr::<CxxWString>::from(U32Str::from_slice(&units));
+        assert_eq!(s.to_str().unwrap(), "hi");
+    }
+
+    #[test]
+    fn from_utf32_str_round_trips() {
+        let owned = Utf32String::from_str("hi");
+        let s = UniquePtr::<CxxWString>::from(owned.as_utfstr());
+        assert_eq!(s.to_str().unwrap(), "hi");
+    }
+
+    #[test]
+    fn from_os_str_round_trips() {
+        let s = UniquePtr::<CxxWString>::from(OsStr::new("hi"));
+        assert_eq!(s.to_str().unwrap(), "hi");
+    }
+
+    #[test]
+    fn try_from_u32_string_round_trips() {
+        let mut stack = StackWString::new();
+        let s = unsafe { stack.init_u32(&['h' as u32, 'i' as u32]) };
+        let result = U32String::try_from(&*s).unwrap();
+        assert_eq!(result, U32String::from_vec(vec!['h' as u32, 'i' as u32]));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn try_from_u32_string_rejects_invalid_unit() {
+        let mut stack = StackWString::new();
+        let s = unsafe { stack.init_u32(&['h' as u32, 0xD800]) };
+        let err = U32String::try_from(&*s).unwrap_err();
+        assert_eq!(err.valid_up_to(), 1);
+    }
+
+    #[test]
+    fn try_from_utf32_string_round_trips() {
+        let mut stack = StackWString::new();
+        let s = unsafe { stack.init_u32(&['h' as u32, 'i' as u32]) };
+        let result = Utf32String::try_from(&*s).unwrap();
+        assert_eq!(result, Utf32String::from_str("hi"));
+    }
+
+    #[test]
+    fn try_from_os_string_round_trips() {
+        let mut stack = StackWString::new();
+        let s = unsafe { stack.init_u32(&['h' as u32, 'i' as u32]) };
+        let result = OsString::try_from(&*s).unwrap();
+        assert_eq!(result, OsString::from("hi"));
+    }
+
+    #[test]
+    fn init_os_str_round_trips() {
+        let mut stack = StackWString::new();
+        let s = unsafe { stack.init_os_str(OsStr::new("hi")) };
+        assert_eq!(s.to_str().unwrap(), "hi");
+    }
+
+    #[test]
+    fn partial_eq_u32_string() {
+        let mut stack = StackWString::new();
+        let s = unsafe { stack.init_u32(&['h' as u32, 'i' as u32]) };
+        let owned = U32String::from_vec(vec!['h' as u32, 'i' as u32]);
+        assert_eq!(*s, owned);
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn decode_utf16_units_checked_reports_raw_offset_after_surrogate_pair() {
+        // A valid supplementary-plane pair (2 raw units, 1 decoded char),
+        // then 'h' (1 unit), then an unpaired lone surrogate: the invalid
+        // unit sits at raw offset 3, not output offset 2.
+        let units = [0xD800, 0xDC00, 'h' as u16, 0xD800];
+        let err = decode_utf16_units_checked(&units).unwrap_err();
+        assert_eq!(err.valid_up_to(), 3);
+    }
+}